@@ -0,0 +1,370 @@
+//! Library API for turning a scanned or hybrid PDF into a searchable one.
+//!
+//! [`ocr_pdf`] is the entry point: it renders image-only pages, runs Tesseract over them, and
+//! reassembles a searchable output PDF. The lower-level [`render_page_to_png`] and
+//! [`run_tesseract`] are exposed separately so this crate can be embedded as a building block in
+//! larger document-indexing pipelines instead of only used as a CLI.
+
+mod preprocess;
+
+use std::fs::File;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+use anyhow::{anyhow, Context, Result};
+use image::{ColorType, ExtendedColorType, ImageEncoder};
+use image::codecs::png::PngEncoder;
+use log::info;
+use pdfium_render::prelude::*;
+use tempfile::TempDir;
+
+/// Pages with at least this many extractable characters are treated as already having a real
+/// text layer and are passed through unchanged instead of being rendered and OCR'd.
+const DIGITAL_TEXT_THRESHOLD: usize = 32;
+
+/// Knobs controlling how [`ocr_pdf`] renders and recognizes pages.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// OCR language(s) passed to Tesseract, e.g. `"eng"` or `"eng+fra"`.
+    pub lang: String,
+    /// Number of pages to render concurrently. Tesseract runs once over all rendered pages.
+    pub jobs: usize,
+    /// Render resolution in dots per inch. Target pixel dimensions are computed from each page's
+    /// real size, so mixed-page-size documents keep their correct aspect ratio.
+    pub dpi: u32,
+    /// Upper bound, in pixels, on either rendered dimension. Pages that would exceed it at the
+    /// requested DPI are scaled down (preserving aspect ratio) to bound memory use.
+    pub max_pixel_dimension: u32,
+    /// Render and OCR every page, even ones that already have a real text layer.
+    pub force_ocr: bool,
+    /// Feed Tesseract a separate (optimized) image for recognition while keeping the original
+    /// full-color rendered page as the visible layer.
+    pub visible_original: bool,
+    /// Convert the OCR image to grayscale before recognition.
+    pub grayscale: bool,
+    /// Binarize the OCR image to pure black/white using Otsu's method before recognition.
+    pub binarize: bool,
+    /// Deskew the OCR image before recognition.
+    pub deskew: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            lang: "eng".to_string(),
+            jobs: 4,
+            dpi: 300,
+            max_pixel_dimension: 6000,
+            force_ocr: false,
+            visible_original: false,
+            grayscale: false,
+            binarize: false,
+            deskew: false,
+        }
+    }
+}
+
+/// Outcome of running [`ocr_pdf`].
+#[derive(Clone, Debug)]
+pub struct Summary {
+    /// Pages rendered and run through Tesseract.
+    pub pages_processed: usize,
+    /// Pages that already had a real text layer and were copied through unchanged.
+    pub pages_skipped: usize,
+    /// Language codes Tesseract was run with.
+    pub languages: Vec<String>,
+}
+
+/// What a worker decided to do with one page: either the page already has a usable text layer
+/// and can be copied through unchanged, or it needs to be rendered to PNG for OCR.
+pub enum PageOutcome {
+    Digital,
+    /// Image-only page. The first path is fed to Tesseract for recognition; the second, when
+    /// `visible_original` is set, is the full-color page image to show in place of it.
+    ImageOnly(PathBuf, Option<PathBuf>),
+}
+
+/// Computes the pixel dimensions to render a page at, derived from its real size in points
+/// (`points / 72 * dpi`) so the render keeps the page's true aspect ratio. Both dimensions are
+/// scaled down together, preserving that ratio, if either would exceed `max_pixel_dimension`.
+fn target_pixel_dimensions(width_pts: f32, height_pts: f32, dpi: u32, max_pixel_dimension: u32) -> (i32, i32) {
+    let width_px = width_pts / 72.0 * dpi as f32;
+    let height_px = height_pts / 72.0 * dpi as f32;
+
+    let scale = (max_pixel_dimension as f32 / width_px.max(height_px)).min(1.0);
+
+    (
+        (width_px * scale).round().max(1.0) as i32,
+        (height_px * scale).round().max(1.0) as i32,
+    )
+}
+
+fn bind_pdfium() -> Result<Pdfium> {
+    let bindings = Pdfium::bind_to_library(
+        Pdfium::pdfium_platform_library_name_at_path("./lib"),
+    ).or_else(|_| Pdfium::bind_to_system_library())?;
+    Ok(Pdfium::new(bindings))
+}
+
+/// Renders `input`'s page at `index` to a PNG for OCR (and, when `options.visible_original` is
+/// set, a second full-color PNG for the visible layer), or reports that the page already has a
+/// real text layer and can be passed through unchanged.
+pub fn render_page_to_png(
+    doc: &PdfDocument,
+    index: usize,
+    temp_dir: &Path,
+    options: &Options,
+) -> Result<PageOutcome> {
+    let page = doc.pages().get(index as u16)?;
+
+    if !options.force_ocr {
+        let text_len = page.text()?.all().trim().chars().count();
+        if text_len >= DIGITAL_TEXT_THRESHOLD {
+            info!("Page {} already has a text layer, passing through unchanged", index + 1);
+            return Ok(PageOutcome::Digital);
+        }
+    }
+
+    info!("Rendering page {}...", index + 1);
+
+    let (target_width, target_height) =
+        target_pixel_dimensions(page.width().value, page.height().value, options.dpi, options.max_pixel_dimension);
+    let rendered_page = page.render_with_config(
+        &PdfRenderConfig::new()
+            .set_target_width(target_width)
+            .set_target_height(target_height),
+    )?;
+    let rgb_image = rendered_page.as_image().to_rgb8();
+
+    let ocr_image_path = temp_dir.join(format!("page_{:04}_ocr.png", index + 1));
+    let visible_image_path = if options.visible_original {
+        // Tesseract recognizes against the preprocessed copy; the full-color render is kept
+        // untouched and overlaid back in as the visible layer during assembly.
+        let ocr_image = preprocess::apply(rgb_image.clone(), options.grayscale, options.binarize, options.deskew);
+        write_png(&ocr_image, &ocr_image_path)?;
+
+        let visible_path = temp_dir.join(format!("page_{:04}_visible.png", index + 1));
+        write_png(&rgb_image.into(), &visible_path)?;
+        Some(visible_path)
+    } else {
+        let ocr_image = preprocess::apply(rgb_image, options.grayscale, options.binarize, options.deskew);
+        write_png(&ocr_image, &ocr_image_path)?;
+        None
+    };
+
+    Ok(PageOutcome::ImageOnly(ocr_image_path, visible_image_path))
+}
+
+/// Binds a fresh Pdfium instance and loads `input`, classifying and (when needed) rendering the
+/// pages in `indices`.
+///
+/// `PdfDocument` (and the `Pdfium` binding it borrows from) is not `Sync`, so each worker thread
+/// gets its own independent binding and its own load of the input file rather than sharing one.
+fn render_pages(
+    input: &Path,
+    temp_dir: &Path,
+    indices: Vec<usize>,
+    options: &Options,
+) -> Result<Vec<(usize, PageOutcome)>> {
+    let pdfium = bind_pdfium()?;
+    let doc = pdfium.load_pdf_from_file(input, None)?;
+
+    indices
+        .into_iter()
+        .map(|index| Ok((index, render_page_to_png(&doc, index, temp_dir, options)?)))
+        .collect()
+}
+
+/// Encodes `image` as a PNG at `path`.
+fn write_png(image: &image::DynamicImage, path: &Path) -> Result<()> {
+    let rgb_image = image.to_rgb8();
+    let mut png_file = File::create(path)?;
+
+    // Create a Vec that lives long enough
+    let mut png_data: Vec<u8> = Vec::new();
+
+    // Pass it to the Cursor
+    let mut cursor = Cursor::new(&mut png_data);
+    let encoder = PngEncoder::new(&mut cursor);
+    encoder.write_image(
+        &rgb_image,
+        rgb_image.width(),
+        rgb_image.height(),
+        ExtendedColorType::from(ColorType::Rgb8),
+    )?;
+    // Save to file
+    png_file.write_all(&cursor.into_inner())?;
+
+    Ok(())
+}
+
+/// Runs Tesseract over `image_paths`, writing a searchable PDF to `output_pdf`. `dpi` is passed
+/// through as `user_defined_dpi` since our PNGs carry no resolution metadata of their own;
+/// without it Tesseract/Leptonica assumes its own default DPI, and the page size (in points) it
+/// assigns to the OCR'd page stops matching the real page we rendered at `dpi`. When `textonly`
+/// is set, Tesseract emits only the invisible text layer (no embedded page image), for callers
+/// that are going to overlay it onto a visible image themselves.
+pub fn run_tesseract(image_paths: &[PathBuf], output_pdf: &Path, lang: &str, dpi: u32, textonly: bool) -> Result<()> {
+    let temp_dir = output_pdf.parent().ok_or_else(|| anyhow!("output_pdf has no parent directory"))?;
+
+    // Tesseract expects a "file list" or individual files
+    // We'll write a temporary file list
+    let file_list_path = temp_dir.join("images.txt");
+    let mut file_list = File::create(&file_list_path)?;
+    for img in image_paths {
+        writeln!(file_list, "{}", img.display())?;
+    }
+
+    // Tesseract command: tesseract file_list output_pdf -l lang -c user_defined_dpi=N [-c textonly_pdf=1] pdf
+    let mut cmd = Command::new("tesseract");
+    cmd.arg(file_list_path);
+    cmd.arg(output_pdf.with_extension(""));
+    cmd.args(&["-l", lang]);
+    cmd.args(&["-c", &format!("user_defined_dpi={}", dpi)]);
+    if textonly {
+        cmd.args(&["-c", "textonly_pdf=1"]);
+    }
+    cmd.arg("pdf");
+
+    let status = cmd.status().context("Failed to run tesseract")?;
+    if !status.success() {
+        return Err(anyhow!("Tesseract OCR failed"));
+    }
+
+    Ok(())
+}
+
+/// Renders `input`'s image-only pages, OCRs them with Tesseract, and writes a searchable PDF to
+/// `output`. Pages that already have a real text layer are copied through unchanged so their
+/// original vector content and fonts survive.
+pub fn ocr_pdf(input: &Path, output: &Path, options: Options) -> Result<Summary> {
+    if !input.exists() {
+        return Err(anyhow!("Input file '{}' does not exist.", input.display()));
+    }
+
+    // Temporary directory for intermediate PNGs
+    let temp_dir: TempDir = tempfile::tempdir().context("Failed to create temporary directory")?;
+    let temp_dir_path = temp_dir.path();
+
+    let pdfium = bind_pdfium()?;
+    let page_count = pdfium.load_pdf_from_file(input, None)?.pages().len() as usize;
+    info!("Loaded PDF with {} pages", page_count);
+
+    // ---- Step 1: Render each page to PNG file, spread across worker threads ----
+    let jobs = options.jobs.max(1).min(page_count.max(1));
+    let mut chunks: Vec<Vec<usize>> = vec![Vec::new(); jobs];
+    for index in 0..page_count {
+        chunks[index % jobs].push(index);
+    }
+
+    let mut outcomes: Vec<(usize, PageOutcome)> = thread::scope(|scope| -> Result<_> {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .filter(|chunk| !chunk.is_empty())
+            .map(|chunk| scope.spawn(|| render_pages(input, temp_dir_path, chunk, &options)))
+            .collect();
+
+        let mut results = Vec::with_capacity(page_count);
+        for handle in handles {
+            let rendered = handle.join().map_err(|_| anyhow!("Render worker thread panicked"))??;
+            results.extend(rendered);
+        }
+        Ok(results)
+    })?;
+
+    // Workers finish out of order; restore deterministic page ordering before OCR.
+    outcomes.sort_by_key(|(index, _)| *index);
+
+    let ocr_image_paths: Vec<PathBuf> = outcomes
+        .iter()
+        .filter_map(|(_, outcome)| match outcome {
+            PageOutcome::ImageOnly(ocr_path, _) => Some(ocr_path.clone()),
+            PageOutcome::Digital => None,
+        })
+        .collect();
+
+    // ---- Step 2: Generate a searchable PDF from the image-only pages with Tesseract ----
+    let ocr_pdf_path = temp_dir_path.join("ocr_pages.pdf");
+    if !ocr_image_paths.is_empty() {
+        info!("Running Tesseract on {} image-only page(s)...", ocr_image_paths.len());
+        run_tesseract(&ocr_image_paths, &ocr_pdf_path, &options.lang, options.dpi, options.visible_original)?;
+    }
+
+    // ---- Step 3: Assemble the output PDF page by page ----
+    info!("Assembling output PDF...");
+
+    let source_doc = pdfium.load_pdf_from_file(input, None)?;
+    let ocr_doc = if !ocr_image_paths.is_empty() {
+        Some(pdfium.load_pdf_from_file(&ocr_pdf_path, None)?)
+    } else {
+        None
+    };
+
+    let mut output_doc = pdfium.create_new_pdf()?;
+    let mut ocr_page_index = 0u16;
+
+    for (index, outcome) in &outcomes {
+        match outcome {
+            PageOutcome::Digital => {
+                let new_index = output_doc.pages().len();
+                output_doc
+                    .pages_mut()
+                    .copy_page_from_document(&source_doc, *index as u16, new_index)?;
+            }
+            PageOutcome::ImageOnly(_, visible_path) => {
+                let ocr_doc = ocr_doc.as_ref().expect("ocr document present when image-only pages exist");
+                let new_index = output_doc.pages().len();
+                output_doc
+                    .pages_mut()
+                    .copy_page_from_document(ocr_doc, ocr_page_index, new_index)?;
+                ocr_page_index += 1;
+
+                if let Some(visible_path) = visible_path {
+                    let mut new_page = output_doc.pages().get(new_index)?;
+                    let visible_image = image::open(visible_path)?;
+                    // Size the overlay to the source page's real dimensions, not the OCR'd
+                    // page's, so the assembled page's physical size always matches the original.
+                    let source_page = source_doc.pages().get(*index as u16)?;
+                    let width = source_page.width();
+                    let height = source_page.height();
+                    new_page.objects_mut().create_image_object(
+                        PdfPoints::ZERO,
+                        PdfPoints::ZERO,
+                        &visible_image,
+                        Some(width),
+                        Some(height),
+                    )?;
+                }
+            }
+        }
+    }
+
+    output_doc.save_to_file(output)?;
+
+    Ok(Summary {
+        pages_processed: ocr_image_paths.len(),
+        pages_skipped: page_count - ocr_image_paths.len(),
+        languages: options.lang.split('+').map(str::to_string).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_pixel_dimensions_matches_dpi_for_a_letter_page() {
+        let (width, height) = target_pixel_dimensions(612.0, 792.0, 300, 10_000);
+        assert_eq!((width, height), (2550, 3300));
+    }
+
+    #[test]
+    fn target_pixel_dimensions_clamps_while_keeping_aspect_ratio() {
+        let (width, height) = target_pixel_dimensions(612.0, 792.0, 1200, 2000);
+        assert_eq!(height, 2000);
+        let ratio = width as f32 / height as f32;
+        assert!((ratio - 612.0 / 792.0).abs() < 0.01);
+    }
+}
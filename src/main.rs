@@ -1,17 +1,10 @@
-use pdfium_render::prelude::*;
-
-use std::fs::File;
-use std::io::{Cursor, Write};
 use std::path::PathBuf;
-use std::process::Command;
-
-use image::{ColorType, ExtendedColorType, ImageEncoder};
-use image::codecs::png::PngEncoder;
 
-use tempfile::TempDir;
-use anyhow::{anyhow, Context, Result};
+use anyhow::Result;
 use clap::Parser;
 
+use leptless_searchable_pdf::{ocr_pdf, Options};
+
 /// Simple PDF OCR tool
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
@@ -27,88 +20,78 @@ struct Args {
     /// OCR language (Tesseract)
     #[arg(short, long, default_value = "eng")]
     lang: String,
-}
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+    /// Number of pages to render concurrently
+    #[arg(short, long, default_value_t = 4)]
+    jobs: usize,
 
-    if !args.input.exists() {
-        return Err(anyhow!("Input file '{}' does not exist.", args.input.display()));
-    }
+    /// Render resolution in dots per inch
+    #[arg(long, default_value_t = 300)]
+    dpi: u32,
 
-    // Temporary directory for intermediate PNGs
-    let temp_dir: TempDir = tempfile::tempdir().context("Failed to create temporary directory")?;
-    let temp_dir_path = temp_dir.path();
-
-    // ---- Init PDFium ----
-    let bindings = Pdfium::bind_to_library(
-        Pdfium::pdfium_platform_library_name_at_path("./lib"),
-    ).or_else(|_| Pdfium::bind_to_system_library())?;
-    let pdfium = Pdfium::new(bindings);
-
-    // ---- Load PDF ----
-    let doc = pdfium.load_pdf_from_file(&args.input, None)?;
-    let page_count = doc.pages().len();
-    println!("Loaded PDF with {} pages", page_count);
-
-    // ---- Step 1: Render each page to PNG file ----
-    let mut image_paths = Vec::new();
-
-    for (index, page) in doc.pages().iter().enumerate() {
-        println!("Rendering page {}...", index + 1);
-
-        // Render page at ~300 DPI
-        let rendered = page.render_with_config(
-            &PdfRenderConfig::new()
-                .set_target_width(2480)
-                .set_target_height(3508),
-        )?;
-        let rgb_image = rendered.as_image().to_rgb8();
-
-        // Encode to PNG on disk
-        let image_path = temp_dir_path.join(format!("page_{:04}.png", index + 1));
-        let mut png_file = File::create(&image_path)?;
-        {
-            // Create a Vec that lives long enough
-            let mut png_data: Vec<u8> = Vec::new();
-
-            // Pass it to the Cursor
-            let mut cursor = Cursor::new(&mut png_data);
-            let encoder = PngEncoder::new(&mut cursor);
-            encoder.write_image(
-                &rgb_image,
-                rgb_image.width(),
-                rgb_image.height(),
-                ExtendedColorType::from(ColorType::Rgb8),
-            )?;
-            // Save to file
-            png_file.write_all(&cursor.into_inner())?;
-        }
+    /// Maximum rendered page dimension, in pixels, to bound memory use
+    #[arg(long, default_value_t = 6000)]
+    max_pixel_dimension: u32,
 
-        image_paths.push(image_path);
-    }
+    /// Render and OCR every page, even ones that already have a real text layer
+    #[arg(long)]
+    force_ocr: bool,
 
-    // ---- Step 2: Generate searchable PDF with Tesseract ----
-    println!("Running Tesseract to create searchable PDF...");
+    /// Feed Tesseract a separate (optimized) image for recognition while keeping the original
+    /// full-color rendered page as the visible layer
+    #[arg(long)]
+    visible_original: bool,
 
-    // Tesseract expects a "file list" or individual files
-    // We'll write a temporary file list
-    let file_list_path = temp_dir_path.join("images.txt");
-    let mut file_list = File::create(&file_list_path)?;
-    for img in &image_paths {
-        writeln!(file_list, "{}", img.display())?;
-    }
+    /// Convert the OCR image to grayscale before recognition
+    #[arg(long)]
+    grayscale: bool,
+
+    /// Binarize the OCR image to pure black/white using Otsu's method before recognition
+    #[arg(long)]
+    binarize: bool,
 
-    // Tesseract command: tesseract file_list output.pdf -l lang pdf
-    let mut cmd = Command::new("tesseract");
-    cmd.arg(file_list_path);
-    cmd.arg(&args.output);
-    cmd.args(&["-l", &args.lang, "pdf"]);
-    let status = cmd.status().context("Failed to run tesseract")?;
-    if !status.success() {
-        return Err(anyhow!("Tesseract OCR failed"));
+    /// Deskew the OCR image before recognition
+    #[arg(long)]
+    deskew: bool,
+}
+
+impl From<Args> for Options {
+    fn from(args: Args) -> Self {
+        Self {
+            lang: args.lang,
+            jobs: args.jobs,
+            dpi: args.dpi,
+            max_pixel_dimension: args.max_pixel_dimension,
+            force_ocr: args.force_ocr,
+            visible_original: args.visible_original,
+            grayscale: args.grayscale,
+            binarize: args.binarize,
+            deskew: args.deskew,
+        }
     }
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .format_target(false)
+        .format_timestamp(None)
+        .init();
+
+    let args = Args::parse();
+    let output = args.output.clone();
+    let input = args.input.clone();
+    let options = Options::from(args);
+
+    let summary = ocr_pdf(&input, &output, options)?;
+
+    println!(
+        "\nâœ… Searchable PDF generated at '{}' ({} page(s) OCR'd, {} page(s) passed through, lang: {})",
+        output.display(),
+        summary.pages_processed,
+        summary.pages_skipped,
+        summary.languages.join("+"),
+    );
 
-    println!("\nâœ… Searchable PDF generated at '{}'", args.output.display());
     Ok(())
 }
@@ -0,0 +1,207 @@
+use image::{DynamicImage, GrayImage, Luma, RgbImage};
+use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
+
+/// How far (in degrees) to sweep when estimating skew, and the step between candidate angles.
+const DESKEW_SWEEP_DEGREES: f64 = 15.0;
+const DESKEW_STEP_DEGREES: f64 = 0.5;
+
+/// Applies the requested preprocessing steps to a rendered page image ahead of OCR.
+///
+/// Deskewing runs first (it needs the original color image to rotate), followed by binarization
+/// (which implies grayscale) or a plain grayscale conversion.
+pub fn apply(rgb_image: RgbImage, grayscale: bool, binarize: bool, deskew: bool) -> DynamicImage {
+    let rgb_image = if deskew { deskew_image(&rgb_image) } else { rgb_image };
+
+    if binarize {
+        let gray = image::imageops::grayscale(&rgb_image);
+        let threshold = otsu_threshold(&gray);
+        DynamicImage::ImageLuma8(binarize_image(&gray, threshold))
+    } else if grayscale {
+        DynamicImage::ImageLuma8(image::imageops::grayscale(&rgb_image))
+    } else {
+        DynamicImage::ImageRgb8(rgb_image)
+    }
+}
+
+/// Picks the intensity threshold that maximizes between-class variance over the image's
+/// 256-bin histogram (Otsu's method).
+fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+
+    let total: u64 = histogram.iter().sum();
+    let sum_all: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, &count)| value as f64 * count as f64)
+        .sum();
+
+    let mut weight_background = 0u64;
+    let mut sum_background = 0.0;
+    // Default to the midpoint rather than 0: a uniform image (no foreground/background split
+    // ever beats the initial 0.0 variance) must still binarize to its own color, not
+    // unconditionally to white.
+    let mut best_threshold = 128u8;
+    let mut best_variance = 0.0;
+
+    for (value, &count) in histogram.iter().enumerate() {
+        weight_background += count;
+        if weight_background == 0 {
+            continue;
+        }
+
+        let weight_foreground = total - weight_background;
+        if weight_foreground == 0 {
+            break;
+        }
+
+        sum_background += value as f64 * count as f64;
+        let mean_background = sum_background / weight_background as f64;
+        let mean_foreground = (sum_all - sum_background) / weight_foreground as f64;
+
+        let variance = weight_background as f64
+            * weight_foreground as f64
+            * (mean_background - mean_foreground).powi(2);
+
+        if variance > best_variance {
+            best_variance = variance;
+            // `value` is the last bin of the dark class; return the first bin of the light class
+            // so `>= threshold` (white) and `< threshold` (ink) split the two classes.
+            best_threshold = (value + 1) as u8;
+        }
+    }
+
+    best_threshold
+}
+
+/// Thresholds a grayscale image to pure black/white at `threshold`.
+fn binarize_image(gray: &GrayImage, threshold: u8) -> GrayImage {
+    GrayImage::from_fn(gray.width(), gray.height(), |x, y| {
+        if gray.get_pixel(x, y)[0] >= threshold {
+            Luma([255])
+        } else {
+            Luma([0])
+        }
+    })
+}
+
+/// Counts ink pixels (darker than `threshold`) in each row of `gray`.
+fn row_ink_counts(gray: &GrayImage, threshold: u8) -> Vec<f64> {
+    (0..gray.height())
+        .map(|y| {
+            (0..gray.width())
+                .filter(|&x| gray.get_pixel(x, y)[0] < threshold)
+                .count() as f64
+        })
+        .collect()
+}
+
+fn variance(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len().max(1) as f64;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len().max(1) as f64
+}
+
+/// Estimates the dominant text skew angle (in degrees) by sweeping candidate rotations and
+/// picking the one whose rotated row-ink-count profile has the highest variance: a page of
+/// horizontal text lines stacks ink into sharp rows once it's levelled, which is exactly what
+/// maximizes that variance.
+fn estimate_skew_angle(gray: &GrayImage, threshold: u8) -> f64 {
+    let mut best_angle = 0.0;
+    let mut best_variance = f64::MIN;
+
+    let steps = (2.0 * DESKEW_SWEEP_DEGREES / DESKEW_STEP_DEGREES).round() as i32;
+    for step in 0..=steps {
+        let angle = -DESKEW_SWEEP_DEGREES + step as f64 * DESKEW_STEP_DEGREES;
+
+        let rotated = rotate_about_center(
+            gray,
+            (angle as f32).to_radians(),
+            Interpolation::Nearest,
+            Luma([255]),
+        );
+
+        let candidate_variance = variance(&row_ink_counts(&rotated, threshold));
+        if candidate_variance > best_variance {
+            best_variance = candidate_variance;
+            best_angle = angle;
+        }
+    }
+
+    best_angle
+}
+
+/// Rotates `rgb_image` back by its estimated skew angle so text lines run horizontally.
+fn deskew_image(rgb_image: &RgbImage) -> RgbImage {
+    let gray = image::imageops::grayscale(rgb_image);
+    let threshold = otsu_threshold(&gray);
+    let angle = estimate_skew_angle(&gray, threshold);
+
+    rotate_about_center(
+        rgb_image,
+        (angle as f32).to_radians(),
+        Interpolation::Bilinear,
+        image::Rgb([255, 255, 255]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn striped_page(size: u32) -> GrayImage {
+        // Horizontal ink stripes every 6px, like lines of text on an otherwise blank page.
+        let mut gray = GrayImage::from_pixel(size, size, Luma([255]));
+        for y in (0..size).step_by(6) {
+            for x in 0..size {
+                gray.put_pixel(x, y, Luma([0]));
+            }
+        }
+        gray
+    }
+
+    #[test]
+    fn otsu_threshold_splits_a_two_tone_image() {
+        let mut gray = GrayImage::new(10, 10);
+        for y in 0..10 {
+            for x in 0..10 {
+                gray.put_pixel(x, y, Luma([if x < 5 { 10 } else { 240 }]));
+            }
+        }
+        let threshold = otsu_threshold(&gray);
+        assert!(threshold > 10 && threshold < 240);
+    }
+
+    #[test]
+    fn binarize_image_keeps_a_uniformly_dark_page_black() {
+        let gray = GrayImage::from_pixel(4, 4, Luma([5]));
+        let threshold = otsu_threshold(&gray);
+        let binarized = binarize_image(&gray, threshold);
+        assert!(binarized.pixels().all(|p| p[0] == 0));
+    }
+
+    #[test]
+    fn binarize_image_keeps_a_uniformly_light_page_white() {
+        let gray = GrayImage::from_pixel(4, 4, Luma([250]));
+        let threshold = otsu_threshold(&gray);
+        let binarized = binarize_image(&gray, threshold);
+        assert!(binarized.pixels().all(|p| p[0] == 255));
+    }
+
+    #[test]
+    fn deskew_image_levels_a_rotated_page() {
+        let rgb = DynamicImage::ImageLuma8(striped_page(60)).to_rgb8();
+        let rotated = rotate_about_center(&rgb, 5.0f32.to_radians(), Interpolation::Nearest, image::Rgb([255, 255, 255]));
+
+        let deskewed = deskew_image(&rotated);
+
+        let deskewed_gray = image::imageops::grayscale(&deskewed);
+        let threshold = otsu_threshold(&deskewed_gray);
+        let residual_angle = estimate_skew_angle(&deskewed_gray, threshold);
+
+        // A correct deskew should cancel out most of the 5 degree rotation. With the sign
+        // flipped, the rotation doubles instead, leaving a residual close to -10 degrees.
+        assert!(residual_angle.abs() < 1.0, "residual skew {residual_angle} too large");
+    }
+}